@@ -1,9 +1,16 @@
+use std::borrow::Borrow;
 use std::cell::RefCell;
+use std::collections::hash_map::RandomState;
 use std::collections::HashMap;
+use std::hash::BuildHasher;
 use std::hash::Hash;
 use std::marker::PhantomData;
 use std::mem;
 use std::ptr::NonNull;
+use std::sync::Mutex;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 struct Item<K, V> {
     key: K,
@@ -18,14 +25,161 @@ struct List<K, V> {
     marker: PhantomData<Box<Item<K, V>>>,
 }
 
-struct Internal<K, V> {
-    map: HashMap<KeyRef<K>, NonNull<Item<K, V>>>,
+struct Internal<K, V, S = RandomState> {
+    map: HashMap<KeyRef<K>, NonNull<Item<K, V>>, S>,
     items: List<K, V>,
     max_len: usize,
 }
 
-pub struct LRU<K, V> {
-    internal: RefCell<Internal<K, V>>,
+pub struct LRU<K, V, S = RandomState> {
+    internal: RefCell<Internal<K, V, S>>,
+}
+
+// `Internal` holds `NonNull<Item<K, V>>` pointers, which are `!Send` by
+// default. Every such pointer is created by `List::push_front` and is
+// reachable only through this `Internal`'s own `map`/`items` fields, so
+// moving an `Internal` to another thread moves exclusive ownership of all
+// the `Item`s it points to along with it; nothing else retains a pointer
+// into it. That makes it sound to send one across threads as long as the
+// `K`/`V` it stores are themselves `Send`.
+unsafe impl<K: Send, V: Send, S: Send> Send for Internal<K, V, S> {}
+
+/// Iterator over `(&K, &V)` pairs in most- to least-recently-used order.
+/// Does not reorder entries.
+///
+/// Holds the `LRU`'s `RefCell` borrowed for as long as the iterator is
+/// alive, so calling a `&self` method that needs `borrow_mut` (e.g.
+/// `get`) while iterating panics instead of silently racing the
+/// traversal. `&mut self` methods such as `put` are rejected at compile
+/// time while the iterator is alive.
+pub struct Iter<'a, K, V, S = RandomState> {
+    next: Option<NonNull<Item<K, V>>>,
+    // Never read directly; kept alive purely so the `RefCell` stays
+    // borrowed for the iterator's lifetime.
+    #[allow(dead_code)]
+    internal: std::cell::Ref<'a, Internal<K, V, S>>,
+}
+
+impl<'a, K, V, S> Iterator for Iter<'a, K, V, S> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|item| unsafe {
+            self.next = item.as_ref().next;
+            (&item.as_ref().key, &item.as_ref().val)
+        })
+    }
+}
+
+/// Iterator over `(&K, &mut V)` pairs in most- to least-recently-used
+/// order. Does not reorder entries.
+///
+/// Holds the `LRU`'s `RefCell` mutably borrowed for as long as the
+/// iterator is alive, for the same reason as `Iter`.
+pub struct IterMut<'a, K, V, S = RandomState> {
+    next: Option<NonNull<Item<K, V>>>,
+    // Never read directly; kept alive purely so the `RefCell` stays
+    // mutably borrowed for the iterator's lifetime.
+    #[allow(dead_code)]
+    internal: std::cell::RefMut<'a, Internal<K, V, S>>,
+}
+
+impl<'a, K, V, S> Iterator for IterMut<'a, K, V, S> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|mut item| unsafe {
+            self.next = item.as_ref().next;
+            (&item.as_ref().key, &mut item.as_mut().val)
+        })
+    }
+}
+
+/// Iterator that empties the cache, yielding owned `(K, V)` pairs in
+/// least- to most-recently-used order.
+pub struct Drain<'a, K, V, S = RandomState> {
+    internal: std::cell::RefMut<'a, Internal<K, V, S>>,
+}
+
+impl<'a, K, V: 'a, S> Iterator for Drain<'a, K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.internal.items.pop_back().map(|item| unsafe {
+            self.internal.map.remove(&KeyRef::new(&item.as_ref().key));
+            let item = Box::from_raw(item.as_ptr());
+            (item.key, item.val)
+        })
+    }
+}
+
+/// A view into a single entry in an `LRU`, obtained from `LRU::entry`.
+pub enum Entry<'a, K, V, S = RandomState> {
+    Occupied(OccupiedEntry<'a, K, V, S>),
+    Vacant(VacantEntry<'a, K, V, S>),
+}
+
+pub struct OccupiedEntry<'a, K, V, S> {
+    item: NonNull<Item<K, V>>,
+    marker: PhantomData<&'a mut Internal<K, V, S>>,
+}
+
+pub struct VacantEntry<'a, K, V, S> {
+    key: K,
+    internal: std::cell::RefMut<'a, Internal<K, V, S>>,
+}
+
+impl<'a, K, V, S> Entry<'a, K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K, V, S> OccupiedEntry<'a, K, V, S> {
+    pub fn get(&self) -> &V {
+        unsafe { &self.item.as_ref().val }
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        unsafe { &mut self.item.as_mut().val }
+    }
+
+    pub fn into_mut(mut self) -> &'a mut V {
+        unsafe { &mut self.item.as_mut().val }
+    }
+}
+
+impl<'a, K, V, S> VacantEntry<'a, K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    pub fn insert(mut self, val: V) -> &'a mut V {
+        let item = self.internal.insert_new(self.key, val);
+        unsafe { &mut (*item.as_ptr()).val }
+    }
 }
 
 impl<K, V> Item<K, V> {
@@ -91,6 +245,19 @@ impl<K, V> List<K, V> {
         }
     }
 
+    fn unlink(&mut self, item: NonNull<Item<K, V>>) {
+        unsafe {
+            match (*item.as_ptr()).prev {
+                Some(prev) => (*prev.as_ptr()).next = (*item.as_ptr()).next,
+                None => self.head = (*item.as_ptr()).next,
+            }
+            match (*item.as_ptr()).next {
+                Some(next) => (*next.as_ptr()).prev = (*item.as_ptr()).prev,
+                None => self.tail = (*item.as_ptr()).prev,
+            }
+        }
+    }
+
     fn pop_back(&mut self) -> Option<NonNull<Item<K, V>>> {
         self.tail.map(|tail| unsafe {
             self.tail = None;
@@ -129,18 +296,64 @@ impl<K: PartialEq> PartialEq for KeyRef<K> {
 
 impl<K: Eq> Eq for KeyRef<K> {}
 
-impl<K, V> Internal<K, V> {
-    fn new(len: usize) -> Self {
+// KeyWrapper lets a `HashMap<KeyRef<K>, _>` be probed with a borrowed `&Q`
+// (e.g. `&str` against a `String` key) without allocating a `K` to look it
+// up with. `KeyRef<K>` borrows as `KeyWrapper<Q>` for any `Q` that `K`
+// itself borrows as, so the hash/eq seen by the map is computed through
+// `K::borrow` and matches what was used when the entry was inserted.
+#[repr(transparent)]
+struct KeyWrapper<K: ?Sized>(K);
+
+impl<K: ?Sized> KeyWrapper<K> {
+    fn from_ref(key: &K) -> &Self {
+        unsafe { &*(key as *const K as *const KeyWrapper<K>) }
+    }
+}
+
+impl<K: ?Sized + Hash> Hash for KeyWrapper<K> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl<K: ?Sized + PartialEq> PartialEq for KeyWrapper<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.eq(&other.0)
+    }
+}
+
+impl<K: ?Sized + Eq> Eq for KeyWrapper<K> {}
+
+impl<K, Q: ?Sized> Borrow<KeyWrapper<Q>> for KeyRef<K>
+where
+    K: Borrow<Q>,
+{
+    fn borrow(&self) -> &KeyWrapper<Q> {
+        KeyWrapper::from_ref(unsafe { (*self.key).borrow() })
+    }
+}
+
+impl<K, V, S> Internal<K, V, S> {
+    fn with_hasher(len: usize, hasher: S) -> Self {
         Self {
-            map: HashMap::with_capacity(len + 1),
+            map: HashMap::with_capacity_and_hasher(len + 1, hasher),
             items: List::new(),
             max_len: len,
         }
     }
+}
+
+impl<K, V, S: Default> Internal<K, V, S> {
+    fn new(len: usize) -> Self {
+        Self::with_hasher(len, S::default())
+    }
+}
 
+impl<K, V, S> Internal<K, V, S> {
     fn put(&mut self, key: K, val: V) -> Option<V>
     where
         K: Hash + Eq,
+        S: BuildHasher,
     {
         if let Some(item) = self.map.get_mut(&KeyRef::new(&key)) {
             let mut val = val;
@@ -151,10 +364,22 @@ impl<K, V> Internal<K, V> {
             return Some(val);
         }
 
+        self.insert_new(key, val);
+        None
+    }
+
+    // Evicts the LRU entry if the cache is already at capacity, then pushes
+    // `key`/`val` to the front. Shared by `put`'s insert path and
+    // `VacantEntry::insert`, so the two don't drift apart.
+    fn insert_new(&mut self, key: K, val: V) -> NonNull<Item<K, V>>
+    where
+        K: Hash + Eq,
+        S: BuildHasher,
+    {
         if self.map.len() >= self.max_len {
             self.items.pop_back().map(|item| unsafe {
                 self.map.remove(&KeyRef::new(&item.as_ref().key));
-                Box::from_raw(item.as_ptr());
+                let _ = Box::from_raw(item.as_ptr());
             });
         }
 
@@ -163,23 +388,75 @@ impl<K, V> Internal<K, V> {
             self.map.insert(KeyRef::new(&item.as_ref().key), item);
         }
 
-        None
+        item
     }
 
-    fn get_item(&mut self, key: &K) -> Option<NonNull<Item<K, V>>>
+    fn get_item<Q: ?Sized + Hash + Eq>(&mut self, key: &Q) -> Option<NonNull<Item<K, V>>>
     where
-        K: Hash + Eq,
+        K: Borrow<Q> + Hash + Eq,
+        S: BuildHasher,
     {
-        if let Some(item) = self.map.get_mut(&KeyRef::new(key)) {
+        if let Some(item) = self.map.get_mut(KeyWrapper::from_ref(key)) {
             self.items.move_to_front(*item);
             return Some(*item);
         }
 
         None
     }
+
+    fn contains<Q: ?Sized + Hash + Eq>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        S: BuildHasher,
+    {
+        self.map.contains_key(KeyWrapper::from_ref(key))
+    }
+
+    // Like `get_item`, but does not promote the entry, so it only needs
+    // `&self` and never touches the recency order.
+    fn peek_item<Q: ?Sized + Hash + Eq>(&self, key: &Q) -> Option<NonNull<Item<K, V>>>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        S: BuildHasher,
+    {
+        self.map.get(KeyWrapper::from_ref(key)).copied()
+    }
+
+    fn remove<Q: ?Sized + Hash + Eq>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        S: BuildHasher,
+    {
+        self.map.remove(KeyWrapper::from_ref(key)).map(|item| unsafe {
+            self.items.unlink(item);
+            Box::from_raw(item.as_ptr()).val
+        })
+    }
+
+    fn resize(&mut self, new_len: usize)
+    where
+        K: Hash + Eq,
+        S: BuildHasher,
+    {
+        while self.map.len() > new_len {
+            match self.items.pop_back() {
+                Some(item) => unsafe {
+                    self.map.remove(&KeyRef::new(&item.as_ref().key));
+                    let _ = Box::from_raw(item.as_ptr());
+                },
+                None => break,
+            }
+        }
+
+        if new_len > self.max_len {
+            self.map.reserve(new_len - self.max_len);
+        }
+
+        self.max_len = new_len;
+    }
 }
 
-impl<K, V> Drop for Internal<K, V> {
+impl<K, V, S> Drop for Internal<K, V, S> {
     fn drop(&mut self) {
         while let Some(item) = self.items.pop_back() {
             Box::from(item.as_ptr());
@@ -187,23 +464,33 @@ impl<K, V> Drop for Internal<K, V> {
     }
 }
 
-impl<K, V> LRU<K, V> {
+impl<K, V> LRU<K, V, RandomState> {
     pub fn new(len: usize) -> Self {
         Self {
             internal: RefCell::new(Internal::new(len)),
         }
     }
+}
+
+impl<K, V, S> LRU<K, V, S> {
+    pub fn with_hasher(len: usize, hasher: S) -> Self {
+        Self {
+            internal: RefCell::new(Internal::with_hasher(len, hasher)),
+        }
+    }
 
     pub fn put(&mut self, key: K, val: V) -> Option<V>
     where
         K: Hash + Eq,
+        S: BuildHasher,
     {
         self.internal.borrow_mut().put(key, val)
     }
 
-    pub fn get(&self, key: &K) -> Option<&V>
+    pub fn get<Q: ?Sized + Hash + Eq>(&self, key: &Q) -> Option<&V>
     where
-        K: Hash + Eq,
+        K: Borrow<Q> + Hash + Eq,
+        S: BuildHasher,
     {
         self.internal
             .borrow_mut()
@@ -211,9 +498,10 @@ impl<K, V> LRU<K, V> {
             .map(|item| unsafe { &item.as_ref().val })
     }
 
-    pub fn get_mut(&mut self, key: &K) -> Option<&mut V>
+    pub fn get_mut<Q: ?Sized + Hash + Eq>(&mut self, key: &Q) -> Option<&mut V>
     where
-        K: Hash + Eq,
+        K: Borrow<Q> + Hash + Eq,
+        S: BuildHasher,
     {
         self.internal
             .borrow_mut()
@@ -221,9 +509,222 @@ impl<K, V> LRU<K, V> {
             .map(|mut item| unsafe { &mut item.as_mut().val })
     }
 
+    pub fn contains<Q: ?Sized + Hash + Eq>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        S: BuildHasher,
+    {
+        self.internal.borrow().contains(key)
+    }
+
+    pub fn remove<Q: ?Sized + Hash + Eq>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        S: BuildHasher,
+    {
+        self.internal.borrow_mut().remove(key)
+    }
+
+    pub fn pop(&mut self, key: &K) -> Option<V>
+    where
+        K: Hash + Eq,
+        S: BuildHasher,
+    {
+        self.remove(key)
+    }
+
+    pub fn peek<Q: ?Sized + Hash + Eq>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        S: BuildHasher,
+    {
+        self.internal
+            .borrow()
+            .peek_item(key)
+            .map(|item| unsafe { &item.as_ref().val })
+    }
+
+    pub fn peek_lru(&self) -> Option<(&K, &V)> {
+        self.internal
+            .borrow()
+            .items
+            .tail
+            .map(|item| unsafe { (&item.as_ref().key, &item.as_ref().val) })
+    }
+
     pub fn count(&self) -> usize {
         self.internal.borrow().map.len()
     }
+
+    pub fn cap(&self) -> usize {
+        self.internal.borrow().max_len
+    }
+
+    pub fn resize(&mut self, new_len: usize)
+    where
+        K: Hash + Eq,
+        S: BuildHasher,
+    {
+        self.internal.borrow_mut().resize(new_len)
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V, S> {
+        let internal = self.internal.borrow();
+        let next = internal.items.head;
+        Iter { next, internal }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V, S> {
+        let internal = self.internal.borrow_mut();
+        let next = internal.items.head;
+        IterMut { next, internal }
+    }
+
+    pub fn drain(&mut self) -> Drain<'_, K, V, S> {
+        Drain {
+            internal: self.internal.borrow_mut(),
+        }
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S>
+    where
+        K: Hash + Eq,
+        S: BuildHasher,
+    {
+        let mut internal = self.internal.borrow_mut();
+        match internal.get_item(&key) {
+            Some(item) => Entry::Occupied(OccupiedEntry {
+                item,
+                marker: PhantomData,
+            }),
+            None => Entry::Vacant(VacantEntry { key, internal }),
+        }
+    }
+}
+
+/// A thread-safe LRU cache sharded across `N` independent `Internal`
+/// caches, each guarded by its own `Mutex`. A key always maps to the same
+/// shard, so `get`/`put`/`pop` only ever contend with operations on other
+/// keys hashing to that shard, giving near-linear scaling under
+/// concurrent access the way sharded concurrent maps do. Each shard's
+/// capacity is `total_len / shard_count`.
+pub struct ConcurrentLRU<K, V, S = RandomState> {
+    shards: Vec<Mutex<Internal<K, V, S>>>,
+    hash_builder: S,
+}
+
+impl<K, V, S: Clone> ConcurrentLRU<K, V, S> {
+    // `total_len` is split evenly across shards; if it doesn't divide
+    // `shard_count` exactly the remainder is dropped, so the effective total
+    // capacity can be slightly less than `total_len` (e.g. 10 over 3 shards
+    // gives 3 per shard, i.e. 9 total).
+    pub fn with_hasher(shard_count: usize, total_len: usize, hasher: S) -> Self {
+        assert!(shard_count > 0, "shard_count must be non-zero");
+
+        let per_shard = total_len / shard_count;
+        let shards = (0..shard_count)
+            .map(|_| Mutex::new(Internal::with_hasher(per_shard, hasher.clone())))
+            .collect();
+
+        Self {
+            shards,
+            hash_builder: hasher,
+        }
+    }
+}
+
+impl<K, V> ConcurrentLRU<K, V, RandomState> {
+    pub fn new(shard_count: usize, total_len: usize) -> Self {
+        Self::with_hasher(shard_count, total_len, RandomState::new())
+    }
+}
+
+impl<K, V, S> ConcurrentLRU<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn shard(&self, key: &K) -> &Mutex<Internal<K, V, S>> {
+        let idx = (self.hash_builder.hash_one(key) as usize) % self.shards.len();
+        &self.shards[idx]
+    }
+
+    pub fn put(&self, key: K, val: V) -> Option<V> {
+        self.shard(&key).lock().unwrap().put(key, val)
+    }
+
+    pub fn get(&self, key: &K) -> Option<V>
+    where
+        V: Clone,
+    {
+        self.shard(key)
+            .lock()
+            .unwrap()
+            .get_item(key)
+            .map(|item| unsafe { item.as_ref().val.clone() })
+    }
+
+    pub fn pop(&self, key: &K) -> Option<V> {
+        self.shard(key).lock().unwrap().remove(key)
+    }
+
+    pub fn count(&self) -> usize {
+        self.shards.iter().map(|shard| shard.lock().unwrap().map.len()).sum()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K, V, S> Serialize for LRU<K, V, S>
+where
+    K: Serialize + Hash + Eq,
+    V: Serialize,
+{
+    fn serialize<Se>(&self, serializer: Se) -> Result<Se::Ok, Se::Error>
+    where
+        Se: Serializer,
+    {
+        #[derive(Serialize)]
+        struct Raw<'a, K, V> {
+            max_len: usize,
+            entries: Vec<(&'a K, &'a V)>,
+        }
+
+        // Walk the list head-to-tail (MRU to LRU) so recency order survives the round trip.
+        Raw {
+            max_len: self.cap(),
+            entries: self.iter().collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V, S> Deserialize<'de> for LRU<K, V, S>
+where
+    K: Deserialize<'de> + Hash + Eq,
+    V: Deserialize<'de>,
+    S: BuildHasher + Default,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Raw<K, V> {
+            max_len: usize,
+            entries: Vec<(K, V)>,
+        }
+
+        let raw = Raw::<K, V>::deserialize(deserializer)?;
+        let mut lru = LRU::with_hasher(raw.max_len, S::default());
+        // Entries were serialized MRU-first; putting them back in reverse
+        // makes the last `put` (the original MRU entry) land at the front.
+        for (key, val) in raw.entries.into_iter().rev() {
+            lru.put(key, val);
+        }
+
+        Ok(lru)
+    }
 }
 
 #[cfg(test)]
@@ -283,4 +784,218 @@ mod tests {
         assert_eq!(None, lru.get(&"key1"));
         assert_eq!(Some(&"val2"), lru.get(&"key2"));
     }
+
+    #[test]
+    fn borrow_lookup() {
+        let mut lru: LRU<String, i32> = LRU::new(2);
+        lru.put("key1".to_string(), 1);
+        lru.put("key2".to_string(), 2);
+
+        assert_eq!(Some(&1), lru.get("key1"));
+        assert_eq!(Some(&mut 2), lru.get_mut("key2"));
+        assert!(lru.contains("key1"));
+        assert!(!lru.contains("key3"));
+
+        assert_eq!(Some(1), lru.remove("key1"));
+        assert!(!lru.contains("key1"));
+        assert_eq!(1, lru.count());
+    }
+
+    #[test]
+    fn pop_and_peek() {
+        let mut lru = LRU::new(2);
+        lru.put("key1", "val1");
+        lru.put("key2", "val2");
+
+        assert_eq!(Some(&"val1"), lru.peek(&"key1"));
+        assert_eq!(Some((&"key1", &"val1")), lru.peek_lru());
+
+        assert_eq!(Some(&"val2"), lru.get(&"key2"));
+        assert_eq!(Some((&"key1", &"val1")), lru.peek_lru());
+
+        assert_eq!(Some("val1"), lru.pop(&"key1"));
+        assert_eq!(None, lru.peek(&"key1"));
+        assert_eq!(1, lru.count());
+        assert_eq!(Some((&"key2", &"val2")), lru.peek_lru());
+    }
+
+    #[test]
+    fn resize() {
+        let mut lru = LRU::new(3);
+        lru.put("key1", "val1");
+        lru.put("key2", "val2");
+        lru.put("key3", "val3");
+        assert_eq!(3, lru.cap());
+
+        lru.resize(2);
+        assert_eq!(2, lru.cap());
+        assert_eq!(2, lru.count());
+        assert_eq!(None, lru.get(&"key1"));
+        assert_eq!(Some(&"val2"), lru.get(&"key2"));
+        assert_eq!(Some(&"val3"), lru.get(&"key3"));
+
+        lru.resize(5);
+        assert_eq!(5, lru.cap());
+        lru.put("key4", "val4");
+        lru.put("key5", "val5");
+        lru.put("key6", "val6");
+        assert_eq!(5, lru.count());
+    }
+
+    #[test]
+    fn iter_and_drain() {
+        let mut lru = LRU::new(3);
+        lru.put("key1", "val1");
+        lru.put("key2", "val2");
+        lru.put("key3", "val3");
+        lru.get(&"key1");
+
+        let collected: Vec<_> = lru.iter().collect();
+        assert_eq!(
+            vec![(&"key1", &"val1"), (&"key3", &"val3"), (&"key2", &"val2")],
+            collected
+        );
+        assert_eq!(3, lru.count());
+
+        for (_, val) in lru.iter_mut() {
+            *val = "updated";
+        }
+        assert_eq!(Some(&"updated"), lru.get(&"key2"));
+
+        let drained: Vec<_> = lru.drain().collect();
+        assert_eq!(3, drained.len());
+        assert_eq!(0, lru.count());
+        assert_eq!(None, lru.get(&"key1"));
+    }
+
+    #[test]
+    #[should_panic(expected = "already borrowed")]
+    fn iter_holds_borrow() {
+        let mut lru = LRU::new(3);
+        lru.put("key1", "val1");
+        lru.put("key2", "val2");
+
+        let mut iter = lru.iter();
+        iter.next();
+        // `iter` is still alive and holds the `RefCell` borrowed, so this
+        // conflicting `get` (which needs `borrow_mut` to promote the entry)
+        // must panic rather than silently racing the live traversal.
+        lru.get(&"key2");
+    }
+
+    #[test]
+    fn with_hasher() {
+        // A `RandomState::new()` would exercise the same hasher `LRU::new`
+        // already uses internally, so use a genuinely different
+        // `BuildHasher` to prove a custom one can be plugged in.
+        #[derive(Default)]
+        struct FnvHasher(u64);
+
+        impl std::hash::Hasher for FnvHasher {
+            fn finish(&self) -> u64 {
+                self.0
+            }
+
+            fn write(&mut self, bytes: &[u8]) {
+                let mut hash = if self.0 == 0 { 0xcbf29ce484222325 } else { self.0 };
+                for &byte in bytes {
+                    hash ^= byte as u64;
+                    hash = hash.wrapping_mul(0x100000001b3);
+                }
+                self.0 = hash;
+            }
+        }
+
+        #[derive(Default)]
+        struct FnvBuildHasher;
+
+        impl std::hash::BuildHasher for FnvBuildHasher {
+            type Hasher = FnvHasher;
+
+            fn build_hasher(&self) -> FnvHasher {
+                FnvHasher::default()
+            }
+        }
+
+        let mut lru = LRU::with_hasher(2, FnvBuildHasher);
+        lru.put("key1", "val1");
+        lru.put("key2", "val2");
+
+        assert_eq!(Some(&"val1"), lru.get(&"key1"));
+        assert_eq!(2, lru.cap());
+    }
+
+    #[test]
+    fn concurrent_lru() {
+        use std::sync::Arc;
+        use std::thread;
+
+        let lru = Arc::new(ConcurrentLRU::new(4, 400));
+
+        let mut handles = Vec::new();
+        for t in 0..4 {
+            let lru = Arc::clone(&lru);
+            handles.push(thread::spawn(move || {
+                for i in 0..10 {
+                    lru.put(t * 10 + i, t * 10 + i);
+                }
+            }));
+        }
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(40, lru.count());
+        for key in 0..40 {
+            assert_eq!(Some(key), lru.get(&key));
+        }
+        assert_eq!(Some(0), lru.pop(&0));
+        assert_eq!(None, lru.get(&0));
+        assert_eq!(39, lru.count());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let mut lru = LRU::new(3);
+        lru.put("key1".to_string(), "val1".to_string());
+        lru.put("key2".to_string(), "val2".to_string());
+        lru.put("key3".to_string(), "val3".to_string());
+        lru.get("key1");
+
+        let json = serde_json::to_string(&lru).unwrap();
+        let restored: LRU<String, String> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(3, restored.cap());
+        assert_eq!(
+            vec![
+                ("key1".to_string(), "val1".to_string()),
+                ("key3".to_string(), "val3".to_string()),
+                ("key2".to_string(), "val2".to_string()),
+            ],
+            restored
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn entry_api() {
+        let mut lru = LRU::new(2);
+
+        *lru.entry("key1").or_insert(0) += 1;
+        *lru.entry("key1").or_insert(0) += 1;
+        assert_eq!(Some(&2), lru.get(&"key1"));
+
+        lru.entry("key2").or_insert_with(|| 10);
+        assert_eq!(Some(&10), lru.get(&"key2"));
+
+        lru.entry("key1").and_modify(|v| *v *= 100).or_insert(0);
+        assert_eq!(Some(&200), lru.get(&"key1"));
+
+        lru.entry("key3").and_modify(|v| *v *= 100).or_insert(3);
+        assert_eq!(Some(&3), lru.get(&"key3"));
+        assert_eq!(2, lru.count());
+    }
 }